@@ -1,27 +1,41 @@
+use futures::StreamExt;
 use itf::trace_from_str;
+use notify::{recommended_watcher, RecursiveMode, Watcher};
 use num_bigint::BigInt;
 use num_traits::cast::ToPrimitive;
 use ratatui::style::{Color, Style};
 use ratatui::widgets::canvas::{Canvas, Points};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::{
-    io::{self},
-    time::Duration,
+use std::path::{Path, PathBuf};
+use std::{io, time::Duration};
+use tokio::sync::mpsc;
+
+use crossterm::event::{Event, EventStream, KeyCode, MouseButton, MouseEventKind};
+use crossterm::{
+    event::EnableMouseCapture,
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
 };
-
-use crossterm::event::{self, EnableMouseCapture, Event, KeyCode};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use crossterm::{execute, ExecutableCommand};
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Layout};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::text::Span;
 use ratatui::widgets::canvas::Line;
-use ratatui::widgets::{Block, Borders};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Terminal;
 
+const DEFAULT_TRACE_PATH: &str = "../out.itf.json";
+
+// Ticks (at the 200ms redraw interval below) a status line stays on screen
+// before clearing itself, so it reads as transient rather than sticky.
+const STATUS_TTL_TICKS: u32 = 15;
+
+// Shared between `draw_dag` and the mouse-click inverse mapping so the two
+// stay in sync.
+const X_BOUNDS: [f64; 2] = [0.0, 110.0];
+const Y_BOUNDS: [f64; 2] = [0.0, 20.0];
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct BlockReference {
     pub authority: BigInt,
@@ -88,6 +102,53 @@ fn coordinates(authority: BigInt, round: BigInt) -> (f64, f64) {
     (x, y)
 }
 
+// Inverse of `coordinates`: recovers the `(authority, round)` a canvas point
+// is closest to. Used to resolve mouse clicks back to a block.
+fn inverse_coordinates(x: f64, y: f64) -> (BigInt, BigInt) {
+    let round = (x / 15.0).round() as i64;
+    let authority = (3.0 - (y - 1.5) / 5.0).round() as i64;
+    (BigInt::from(authority), BigInt::from(round))
+}
+
+fn dag_layout(area: Rect) -> (Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .split(area);
+    (chunks[0], chunks[1])
+}
+
+// Returns `None` if the click landed outside the canvas's interior.
+fn screen_to_canvas(area: Rect, column: u16, row: u16) -> Option<(f64, f64)> {
+    let inner_x = area.x + 1;
+    let inner_y = area.y + 1;
+    let inner_width = area.width.saturating_sub(2);
+    let inner_height = area.height.saturating_sub(2);
+    if inner_width == 0 || inner_height == 0 {
+        return None;
+    }
+    if column < inner_x
+        || column >= inner_x + inner_width
+        || row < inner_y
+        || row >= inner_y + inner_height
+    {
+        return None;
+    }
+
+    let rel_x = (column - inner_x) as f64 / inner_width as f64;
+    let rel_y = (row - inner_y) as f64 / inner_height as f64;
+    let x = X_BOUNDS[0] + rel_x * (X_BOUNDS[1] - X_BOUNDS[0]);
+    let y = Y_BOUNDS[1] - rel_y * (Y_BOUNDS[1] - Y_BOUNDS[0]);
+    Some((x, y))
+}
+
+fn find_block<'a>(
+    blocks: &'a BlockStore,
+    reference: &BlockReference,
+) -> Option<&'a StatementBlock> {
+    blocks.get(&reference.round)?.get(&reference.authority)
+}
+
 fn color_from_status(status: ProposerSlotState) -> ratatui::prelude::Color {
     match status {
         ProposerSlotState::Commit => Color::Green,
@@ -115,13 +176,19 @@ fn show_log(log: Log) -> String {
     }
 }
 
-fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision]) {
-    let chunks = Layout::default()
-        .constraints(vec![Constraint::Percentage(100)])
-        .split(f.size());
+fn draw_dag(
+    f: &mut ratatui::Frame,
+    blocks: &BlockStore,
+    decisions: &[Decision],
+    status: Option<&str>,
+    state_index: usize,
+    state_count: usize,
+    selected: Option<&StatementBlock>,
+) {
+    let (dag_area, panel_area) = dag_layout(f.size());
 
     let mut edges: Vec<Line> = Vec::new();
-    let decision = decisions.last().unwrap();
+    let decision = decisions.last();
     blocks.iter().for_each(|(round, blocks)| {
         blocks.iter().for_each(|(authority, block)| {
             let (x, y) = coordinates(authority.clone(), round.clone());
@@ -130,10 +197,10 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
                 let (ix, iy) = coordinates(parent.authority.clone(), parent.round.clone());
 
                 // Color certified edges in green
-                let color = match decision.log.clone() {
-                    Log::DirectDecision(DirectDecisionFields {
+                let color = match decision.map(|decision| decision.log.clone()) {
+                    Some(Log::DirectDecision(DirectDecisionFields {
                         supporting_edges, ..
-                    }) => {
+                    })) => {
                         if supporting_edges.iter().any(|(a, b)| {
                             (*a == *parent.label && *b == block.reference.label)
                                 || (*a == block.reference.label && *b == *parent.label)
@@ -143,7 +210,7 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
                             None
                         }
                     }
-                    Log::IndirectDecision(IndirectDecisionFields { edges, .. }) => {
+                    Some(Log::IndirectDecision(IndirectDecisionFields { edges, .. })) => {
                         if edges.clone().iter().any(|(a, b)| {
                             (*a == *parent.label && *b == block.reference.label)
                                 || (*a == block.reference.label && *b == *parent.label)
@@ -169,7 +236,11 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
     });
 
     let canvas = Canvas::default()
-        .block(Block::default().borders(Borders::ALL).title("DAG"))
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "DAG (state {}/{})",
+            state_index + 1,
+            state_count
+        )))
         .paint(|ctx| {
             // Draw edges
             for edge in edges.clone() {
@@ -192,6 +263,14 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
                 );
             }
 
+            if let Some(status) = status {
+                ctx.print(
+                    15.0,
+                    19.0,
+                    Span::styled(status.to_string(), Style::default().fg(Color::Yellow)),
+                );
+            }
+
             // Draw nodes
             blocks.iter().for_each(|(round, blocks)| {
                 blocks.iter().for_each(|(authority, block)| {
@@ -206,12 +285,13 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
                         })
                         .unwrap_or(Color::Gray);
 
-                    if let Log::IndirectDecision(IndirectDecisionFields { anchor, .. }) =
-                        decision.log.clone()
+                    if let Some(Log::IndirectDecision(IndirectDecisionFields { anchor, .. })) =
+                        decision.map(|decision| decision.log.clone())
                     {
                         if anchor == block.reference.label {
                             // Changing color results in not being able to see the anchor's decision status
                             // color = Color::Yellow;
+                            let decision = decision.expect("anchor log implies a decision");
                             ctx.print(
                                 18.0,
                                 17.0,
@@ -237,16 +317,293 @@ fn draw_dag(f: &mut ratatui::Frame, blocks: &BlockStore, decisions: &[Decision])
                 });
             });
         })
-        .x_bounds([0.0, 110.0])
-        .y_bounds([0.0, 20.0]);
+        .x_bounds(X_BOUNDS)
+        .y_bounds(Y_BOUNDS);
+
+    f.render_widget(canvas, dag_area);
+    draw_inspector(f, panel_area, selected, decisions);
+}
+
+fn draw_inspector(
+    f: &mut ratatui::Frame,
+    area: Rect,
+    selected: Option<&StatementBlock>,
+    decisions: &[Decision],
+) {
+    let text = match selected {
+        None => "Click a node in the DAG to inspect it.".to_string(),
+        Some(block) => {
+            let mut lines = vec![
+                format!("Block: {}", block.reference.label),
+                format!("Authority: {}", block.reference.authority),
+                format!("Round: {}", block.reference.round),
+                String::new(),
+                "Parents:".to_string(),
+            ];
+            if block.parents.is_empty() {
+                lines.push("  (none)".to_string());
+            } else {
+                for parent in &block.parents {
+                    lines.push(format!("  {}", parent.label));
+                }
+            }
+
+            if let Some(decision) = decisions.iter().find(|d| d.block == block.reference) {
+                lines.push(String::new());
+                lines.push(format!("Status: {:?}", decision.status));
+                lines.push("Log:".to_string());
+                lines.push(format!("{:#?}", decision.log));
+            }
+
+            lines.join("\n")
+        }
+    };
+
+    let panel = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Inspector"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(panel, area);
+}
+
+// Graphviz color name matching the `ratatui` color used for the same node
+// or edge in `draw_dag`, so the exported DOT file looks like the canvas.
+// White (the canvas's plain edge color) and any other color map to black.
+fn graphviz_color(color: Color) -> &'static str {
+    match color {
+        Color::Green => "green",
+        Color::Red => "red",
+        Color::Blue => "blue",
+        Color::Gray => "gray",
+        _ => "black",
+    }
+}
+
+fn node_color(block: &StatementBlock, decisions: &[Decision]) -> Color {
+    decisions
+        .iter()
+        .find_map(|decision| {
+            if decision.block == block.reference {
+                Some(color_from_status(decision.status.clone()))
+            } else {
+                None
+            }
+        })
+        .unwrap_or(Color::Gray)
+}
+
+fn edge_color(
+    parent: &BlockReference,
+    block: &StatementBlock,
+    decision: Option<&Decision>,
+) -> Color {
+    let on_edge = |a: &str, b: &str| {
+        (a == parent.label && b == block.reference.label)
+            || (a == block.reference.label && b == parent.label)
+    };
+    match decision.map(|decision| &decision.log) {
+        Some(Log::DirectDecision(DirectDecisionFields {
+            supporting_edges, ..
+        })) => supporting_edges
+            .iter()
+            .any(|(a, b)| on_edge(a, b))
+            .then_some(Color::Green),
+        Some(Log::IndirectDecision(IndirectDecisionFields { edges, .. })) => edges
+            .iter()
+            .any(|(a, b)| on_edge(a, b))
+            .then_some(Color::Green),
+        _ => None,
+    }
+    .unwrap_or(Color::White)
+}
+
+// One `rank=same` cluster per round, colored as `draw_dag` colors the canvas
+// (via `graphviz_color`) so the exported DOT/SVG matches what's on screen.
+fn render_dot(blocks: &BlockStore, decisions: &[Decision]) -> String {
+    let decision = decisions.last();
+
+    let mut rounds: Vec<&BigInt> = blocks.keys().collect();
+    rounds.sort();
+
+    let mut dot =
+        String::from("digraph dag {\n    rankdir=LR;\n    node [shape=box, style=filled];\n\n");
+
+    for round in &rounds {
+        let mut authorities: Vec<&BigInt> = blocks[*round].keys().collect();
+        authorities.sort();
+
+        dot.push_str("    { rank=same;\n");
+        for authority in authorities {
+            let block = &blocks[*round][authority];
+            dot.push_str(&format!(
+                "        \"{}\" [fillcolor={}];\n",
+                block.reference.label,
+                graphviz_color(node_color(block, decisions))
+            ));
+        }
+        dot.push_str("    }\n");
+    }
+    dot.push('\n');
+
+    for round in &rounds {
+        let mut authorities: Vec<&BigInt> = blocks[*round].keys().collect();
+        authorities.sort();
+
+        for authority in authorities {
+            let block = &blocks[*round][authority];
+            for parent in &block.parents {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [color={}];\n",
+                    block.reference.label,
+                    parent.label,
+                    graphviz_color(edge_color(parent, block, decision))
+                ));
+            }
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+// Writes the currently displayed DAG to a `.dot` file next to the trace,
+// and rasterizes it to SVG with the `dot` binary when one is on `PATH`.
+// Returns the path to whichever artifact is the most useful one produced.
+fn export_dag(
+    trace_path: &Path,
+    blocks: &BlockStore,
+    decisions: &[Decision],
+) -> Result<PathBuf, String> {
+    let dir = trace_path.parent().unwrap_or_else(|| Path::new("."));
+    let dot_path = dir.join("dag-export.dot");
+    std::fs::write(&dot_path, render_dot(blocks, decisions))
+        .map_err(|e| format!("failed to write {}: {e}", dot_path.display()))?;
+
+    let svg_path = dot_path.with_extension("svg");
+    let rendered = std::process::Command::new("dot")
+        .arg("-Tsvg")
+        .arg(&dot_path)
+        .arg("-o")
+        .arg(&svg_path)
+        .status();
+    match rendered {
+        Ok(status) if status.success() => Ok(svg_path),
+        _ => Ok(dot_path),
+    }
+}
+
+// Returns a human-readable error on failure so the caller can surface it
+// instead of crashing on a partial write from the model checker.
+fn load_trace(path: &Path) -> Result<itf::Trace<State>, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    trace_from_str(&data).map_err(|e| format!("failed to parse {}: {e}", path.display()))
+}
+
+// Decisions come back newest-last so that `decisions[0..=i]` replays them
+// in the order they were made.
+fn state_blocks_decisions(
+    trace: &itf::Trace<State>,
+    state_index: usize,
+) -> (BlockStore, Vec<Decision>) {
+    let state = &trace.states[state_index].value;
+    let decisions = state.decisions.iter().cloned().rev().collect();
+    (state.blocks.clone(), decisions)
+}
+
+// Decoupled from where they came from (keyboard, mouse, the file watcher,
+// or the redraw tick) so the consumer loop below only has to apply them.
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    StepForward,
+    StepBack,
+    NextState,
+    PrevState,
+    Select(u16, u16),
+    Export,
+    Reload,
+    Tick,
+    Quit,
+}
 
-    f.render_widget(canvas, chunks[0]);
+fn event_to_action(event: Event) -> Option<Action> {
+    match event {
+        Event::Key(key) => match key.code {
+            KeyCode::Char('q') => Some(Action::Quit),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::StepForward),
+            KeyCode::Char('h') | KeyCode::Left => Some(Action::StepBack),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::NextState),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::PrevState),
+            KeyCode::Char('e') => Some(Action::Export),
+            _ => None,
+        },
+        Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+            Some(Action::Select(mouse.column, mouse.row))
+        }
+        _ => None,
+    }
 }
 
-fn main() -> Result<(), io::Error> {
-    // load trace data
-    let data = include_str!("../../out.itf.json");
-    let trace: itf::Trace<State> = trace_from_str(data).unwrap();
+#[tokio::main]
+async fn main() -> Result<(), io::Error> {
+    let path: PathBuf = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_TRACE_PATH));
+
+    let mut trace = load_trace(&path).expect("failed to load initial trace");
+
+    // Watch the trace file so Quint/Apalache re-runs show up without a
+    // restart. `notify`'s callback is synchronous, so a dedicated thread
+    // bridges it onto the async `Action` channel the consumer loop below reads.
+    let (watch_tx, watch_rx) = std::sync::mpsc::channel();
+    let mut watcher = recommended_watcher(watch_tx).expect("failed to create file watcher");
+    watcher
+        .watch(&path, RecursiveMode::NonRecursive)
+        .expect("failed to watch trace file");
+
+    let (action_tx, mut action_rx) = mpsc::unbounded_channel::<Action>();
+
+    {
+        let action_tx = action_tx.clone();
+        std::thread::spawn(move || {
+            while watch_rx.recv().is_ok() {
+                // A single save can fire several `notify` events; wait a
+                // moment and drain the rest of the burst so it collapses
+                // into one `Reload` instead of re-reading the file per event.
+                std::thread::sleep(Duration::from_millis(50));
+                while watch_rx.try_recv().is_ok() {}
+                if action_tx.send(Action::Reload).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    tokio::spawn({
+        let action_tx = action_tx.clone();
+        async move {
+            let mut events = EventStream::new();
+            let mut tick = tokio::time::interval(Duration::from_millis(200));
+            loop {
+                tokio::select! {
+                    event = events.next() => {
+                        let Some(Ok(event)) = event else { break };
+                        if let Some(action) = event_to_action(event) {
+                            if action_tx.send(action).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    _ = tick.tick() => {
+                        if action_tx.send(Action::Tick).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    });
+    drop(action_tx);
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -255,36 +612,102 @@ fn main() -> Result<(), io::Error> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let last_state = trace.states.last().expect("Can't find a state");
-    let blocks = &last_state.value.blocks;
-    let decisions: Vec<Decision> = last_state.value.decisions.iter().cloned().rev().collect();
-
+    let mut state_index = trace.states.len().checked_sub(1).expect("trace has no states");
+    let (mut blocks, mut decisions) = state_blocks_decisions(&trace, state_index);
+    let mut status: Option<String> = None;
+    let mut status_ttl: u32 = 0;
+    let mut selected: Option<BlockReference> = None;
     let mut i = 0;
-    loop {
-        if i >= decisions.len() {
-            return restore_terminal();
-        }
-
-        terminal.draw(|f| draw_dag(f, blocks, &decisions[0..=i]))?;
 
-        if crossterm::event::poll(Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => return restore_terminal(),
-                    KeyCode::Char('l') | KeyCode::Right => {
-                        i += 1;
+    while let Some(action) = action_rx.recv().await {
+        match action {
+            Action::Quit => break,
+            Action::StepForward => {
+                i += 1;
+            }
+            Action::StepBack => {
+                if i > 1 {
+                    i -= 1;
+                }
+            }
+            Action::NextState => {
+                if state_index + 1 < trace.states.len() {
+                    state_index += 1;
+                    (blocks, decisions) = state_blocks_decisions(&trace, state_index);
+                }
+            }
+            Action::PrevState => {
+                if state_index > 0 {
+                    state_index -= 1;
+                    (blocks, decisions) = state_blocks_decisions(&trace, state_index);
+                }
+            }
+            Action::Select(column, row) => {
+                let (dag_area, _) = dag_layout(terminal.size()?);
+                if let Some((x, y)) = screen_to_canvas(dag_area, column, row) {
+                    let (authority, round) = inverse_coordinates(x, y);
+                    selected = blocks
+                        .get(&round)
+                        .and_then(|by_authority| by_authority.get(&authority))
+                        .map(|block| block.reference.clone());
+                }
+            }
+            Action::Export => {
+                let shown = &decisions[0..decisions.len().min(i + 1)];
+                status = Some(match export_dag(&path, &blocks, shown) {
+                    Ok(path) => format!("exported DAG to {}", path.display()),
+                    Err(err) => format!("export failed: {err}"),
+                });
+                status_ttl = STATUS_TTL_TICKS;
+            }
+            Action::Reload => {
+                status = Some(match load_trace(&path) {
+                    Ok(new_trace) if new_trace.states.is_empty() => {
+                        "reload failed: trace has no states".to_string()
                     }
-                    KeyCode::Char('h') | KeyCode::Left => {
-                        if i > 1 {
-                            i -= 1;
-                        }
+                    Ok(new_trace) => {
+                        trace = new_trace;
+                        state_index = state_index.min(trace.states.len() - 1);
+                        (blocks, decisions) = state_blocks_decisions(&trace, state_index);
+                        "trace reloaded".to_string()
+                    }
+                    Err(err) => format!("reload failed: {err}"),
+                });
+                status_ttl = STATUS_TTL_TICKS;
+            }
+            Action::Tick => {
+                if status.is_some() {
+                    status_ttl = status_ttl.saturating_sub(1);
+                    if status_ttl == 0 {
+                        status = None;
                     }
-                    _ => {}
                 }
             }
         }
+
+        // A trace state may have fewer decisions than the one we were on, so
+        // clamp rather than bailing out of the loop entirely.
+        i = i.min(decisions.len().saturating_sub(1));
+        let selected_block = selected
+            .as_ref()
+            .and_then(|reference| find_block(&blocks, reference));
+
+        terminal.draw(|f| {
+            draw_dag(
+                f,
+                &blocks,
+                &decisions[0..decisions.len().min(i + 1)],
+                status.as_deref(),
+                state_index,
+                trace.states.len(),
+                selected_block,
+            )
+        })?;
     }
+
+    restore_terminal()
 }
+
 fn restore_terminal() -> io::Result<()> {
     disable_raw_mode()?;
     io::stdout().execute(LeaveAlternateScreen)?;